@@ -0,0 +1,30 @@
+//! Dew point and absolute humidity derived from a temperature/relative-humidity pair.
+
+/// Magnus approximation coefficients (valid over typical ambient ranges).
+const MAGNUS_A: f32 = 17.62;
+const MAGNUS_B: f32 = 243.12;
+
+/// Smallest relative humidity (%) we'll take a logarithm of, to avoid `ln(0)`.
+const MIN_RH_PERCENT: f32 = 1e-3;
+
+/// Dew point in °C, via the Magnus approximation.
+///
+/// `temperature_c` is the air temperature in °C and `humidity_percent` is the
+/// relative humidity in percent (0-100). `humidity_percent` is clamped away
+/// from zero since `ln(RH/100)` is undefined there.
+pub fn dew_point_celsius(temperature_c: f32, humidity_percent: f32) -> f32 {
+    let rh = humidity_percent.max(MIN_RH_PERCENT);
+    let gamma = (MAGNUS_A * temperature_c) / (MAGNUS_B + temperature_c) + (rh / 100.0).ln();
+    (MAGNUS_B * gamma) / (MAGNUS_A - gamma)
+}
+
+/// Absolute humidity in g/m³.
+///
+/// `temperature_c` is the air temperature in °C and `humidity_percent` is the
+/// relative humidity in percent (0-100).
+pub fn absolute_humidity_g_per_m3(temperature_c: f32, humidity_percent: f32) -> f32 {
+    let rh = humidity_percent.max(MIN_RH_PERCENT);
+    let saturation_vapor_pressure =
+        6.112 * ((MAGNUS_A * temperature_c) / (MAGNUS_B + temperature_c)).exp();
+    216.7 * ((rh / 100.0) * saturation_vapor_pressure) / (273.15 + temperature_c)
+}
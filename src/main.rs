@@ -1,5 +1,12 @@
+mod humidity;
+mod orientation;
+mod telemetry;
+
+use std::time::Instant;
+
 use anyhow::Result;
 use embedded_hal::delay::DelayNs; // Note: DelayMs is now part of the DelayNs trait
+use embedded_hal::i2c::I2c;
 use esp_idf_svc::hal::{
     delay::FreeRtos,
     i2c::{I2cConfig, I2cDriver},
@@ -15,6 +22,105 @@ use embedded_hal_bus::i2c::CriticalSectionDevice;
 use shtc3::{self, PowerMode as shtPowerMode, Shtc3};
 // ANCHOR_END: new_imports
 
+/// User-tunable knobs for the SHTC3 sampling loop.
+///
+/// `power_mode` selects the measurement command `measure_raw_checked` issues,
+/// `sleep_between_samples` decides whether the sensor is put to sleep
+/// between readings (trading a bit of wakeup latency for much lower standby
+/// current), `sample_interval_ms` replaces the hard-coded 500 ms delay, and
+/// `telemetry_summary_interval` is how many samples elapse between the
+/// telemetry aggregator's summary lines.
+struct SensorConfig {
+    power_mode: shtPowerMode,
+    sleep_between_samples: bool,
+    sample_interval_ms: u32,
+    telemetry_summary_interval: u32,
+}
+
+impl Default for SensorConfig {
+    fn default() -> Self {
+        Self {
+            power_mode: shtPowerMode::NormalMode,
+            sleep_between_samples: true,
+            sample_interval_ms: 500,
+            telemetry_summary_interval: 20,
+        }
+    }
+}
+
+/// SHTC3 I2C address (7-bit).
+const SHTC3_ADDRESS: u8 = 0x70;
+/// Normal-mode measurement command, clock stretching disabled, T first.
+const SHTC3_MEASURE_CMD_NORMAL: [u8; 2] = [0x78, 0x66];
+/// Low-power-mode measurement command, clock stretching disabled, T first.
+const SHTC3_MEASURE_CMD_LOW_POWER: [u8; 2] = [0x60, 0x9c];
+
+/// Errors that can occur while validating a raw SHTC3 reading.
+#[derive(Debug)]
+enum ShtcReadError<E> {
+    /// The underlying I2C transaction failed.
+    I2c(E),
+    /// A received byte pair did not match its trailing CRC-8 checksum.
+    ChecksumError,
+}
+
+/// CRC-8 over `bytes` using the SHTC3's checksum parameters: polynomial
+/// 0x31 (x^8+x^5+x^4+1), init 0xFF, no input/output reflection, no final XOR.
+fn crc8(bytes: &[u8]) -> u8 {
+    let mut crc = 0xFFu8;
+    for &byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x31
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Issues the SHTC3 measurement command directly, waits out the conversion
+/// time for `power_mode`, and validates the CRC-8 on both the temperature
+/// and humidity words before converting them to physical units. This is the
+/// *only* measurement taken per iteration — a corrupted sample is surfaced
+/// as an error instead of being silently converted into a bogus
+/// temperature/humidity pair, and there's no second conversion cycle on top
+/// of it to double the sensor's duty cycle.
+fn measure_raw_checked<I2C, D>(
+    i2c: &mut I2C,
+    delay: &mut D,
+    power_mode: shtPowerMode,
+) -> Result<(f32, f32), ShtcReadError<I2C::Error>>
+where
+    I2C: I2c,
+    D: DelayNs,
+{
+    let (cmd, conversion_time_ms) = match power_mode {
+        shtPowerMode::NormalMode => (SHTC3_MEASURE_CMD_NORMAL, 15),
+        shtPowerMode::LowPower => (SHTC3_MEASURE_CMD_LOW_POWER, 2),
+    };
+
+    i2c.write(SHTC3_ADDRESS, &cmd).map_err(ShtcReadError::I2c)?;
+    delay.delay_ms(conversion_time_ms);
+
+    let mut buf = [0u8; 6];
+    i2c.read(SHTC3_ADDRESS, &mut buf)
+        .map_err(ShtcReadError::I2c)?;
+
+    if crc8(&buf[0..2]) != buf[2] || crc8(&buf[3..5]) != buf[5] {
+        return Err(ShtcReadError::ChecksumError);
+    }
+
+    let temperature_raw = u16::from_be_bytes([buf[0], buf[1]]);
+    let humidity_raw = u16::from_be_bytes([buf[3], buf[4]]);
+
+    // Sensirion's standard raw-to-physical conversion.
+    let temperature_c = -45.0 + 175.0 * (temperature_raw as f32 / 65536.0);
+    let humidity_percent = 100.0 * (humidity_raw as f32 / 65536.0);
+    Ok((temperature_c, humidity_percent))
+}
 
 fn main() -> Result<()> {
     esp_idf_svc::sys::link_patches();
@@ -31,9 +137,12 @@ fn main() -> Result<()> {
     let bus = shared_bus::new_std!(I2cDriver<'_>).unwrap();
     // ANCHOR_END: bus_manager
 
-    // 2. Acquire two independent proxies to the bus.
+    // 2. Acquire independent proxies to the bus: one each for the SHTC3 and
+    // the IMU, plus a third the main loop uses to take the CRC-checked raw
+    // SHTC3 measurement.
     let proxy_1 = bus.acquire_i2c();
     let proxy_2 = bus.acquire_i2c();
+    let mut proxy_3 = bus.acquire_i2c();
 
     // ANCHOR: shtc3_driver
     // 3. Instantiate the SHTC3 driver using its `new` method.
@@ -48,29 +157,103 @@ fn main() -> Result<()> {
     let mut imu = Icm42670::new(proxy_2, Address::Primary).unwrap();
     let device_id = imu.device_id().unwrap();
     println!("Device ID ICM42670p: {:#02x}", device_id);
-    
-    imu.set_power_mode(imuPowerMode::GyroLowNoise).unwrap();
+
+    // Keep both the gyro and accelerometer active so we can fuse them below.
+    imu.set_power_mode(imuPowerMode::SixAxisLowNoise).unwrap();
+
+    let sensor_config = SensorConfig::default();
+    let mut telemetry = telemetry::Telemetry::new(sensor_config.telemetry_summary_interval);
+    let mut filter = orientation::ComplementaryFilter::new();
+    let mut last_sample_at = Instant::now();
 
     loop {
-        // 6. Read gyro data (unchanged).
+        // 6. Read gyro and accelerometer data.
         let gyro_data = imu.gyro_norm().unwrap();
+        let accel_data = imu.accel_norm().unwrap();
+
+        let now = Instant::now();
+        let dt_s = (now - last_sample_at).as_secs_f32();
+        last_sample_at = now;
+        filter.update(
+            accel_data.x,
+            accel_data.y,
+            accel_data.z,
+            gyro_data.y,
+            gyro_data.x,
+            dt_s,
+        );
+
+        // Wake the SHTC3 from sleep before taking a measurement. On the very
+        // first iteration the sensor is already awake, but issuing a wakeup
+        // command while awake is a no-op per the datasheet, so it's safe to
+        // always send it when sleeping is enabled.
+        if sensor_config.sleep_between_samples {
+            sht.wakeup(&mut FreeRtos).unwrap();
+        }
 
         // ANCHOR: shtc3_measurement
-        // 7. The shtc3 driver performs a measurement in a single blocking call.
-        // It requires a delay provider to wait for the measurement to complete.
-        let measurement = sht.measure(shtPowerMode::NormalMode, &mut FreeRtos).unwrap();
+        // 7. Take the single CRC-checked SHTC3 measurement for this
+        // iteration; a checksum failure discards the sample instead of
+        // handing back a bogus temperature/humidity pair.
+        let sht_reading =
+            measure_raw_checked(&mut proxy_3, &mut FreeRtos, sensor_config.power_mode);
         // ANCHOR_END: shtc3_measurement
 
+        // Put the SHTC3 back to sleep between samples to cut standby current.
+        if sensor_config.sleep_between_samples {
+            sht.sleep().unwrap();
+        }
+
+        let (temperature_c, humidity_percent) = match sht_reading {
+            Ok(values) => values,
+            Err(ShtcReadError::ChecksumError) => {
+                println!("SHTC3 checksum error: discarding sample");
+                FreeRtos.delay_ms(sensor_config.sample_interval_ms);
+                continue;
+            }
+            Err(ShtcReadError::I2c(_)) => {
+                println!("SHTC3 raw read failed: I2C error");
+                FreeRtos.delay_ms(sensor_config.sample_interval_ms);
+                continue;
+            }
+        };
+
+        let dew_point_c = humidity::dew_point_celsius(temperature_c, humidity_percent);
+        let absolute_humidity =
+            humidity::absolute_humidity_g_per_m3(temperature_c, humidity_percent);
+
         // 8. Print all values.
         println!(
-            "TEMP: {:.2} °C | HUM: {:.2} % | GYRO: X= {:.2}  Y= {:.2}  Z= {:.2}",
-            measurement.temperature.as_degrees_celsius(),
-            measurement.humidity.as_percent(),
+            "TEMP: {:.2} °C | HUM: {:.2} % | DEW: {:.2} °C | AH: {:.2} g/m³ | GYRO: X= {:.2}  Y= {:.2}  Z= {:.2} | PITCH: {:.2}° | ROLL: {:.2}°",
+            temperature_c,
+            humidity_percent,
+            dew_point_c,
+            absolute_humidity,
             gyro_data.x,
             gyro_data.y,
             gyro_data.z,
+            filter.pitch_deg(),
+            filter.roll_deg(),
         );
 
-        FreeRtos.delay_ms(500u32);
+        let gyro_magnitude =
+            (gyro_data.x * gyro_data.x + gyro_data.y * gyro_data.y + gyro_data.z * gyro_data.z)
+                .sqrt();
+        if let Some(summary) = telemetry.record(temperature_c, humidity_percent, gyro_magnitude) {
+            println!(
+                "SUMMARY | TEMP min={:.2} max={:.2} mean={:.2} | HUM min={:.2} max={:.2} mean={:.2} | GYRO|.| min={:.2} max={:.2} mean={:.2}",
+                summary.temperature_min,
+                summary.temperature_max,
+                summary.temperature_mean,
+                summary.humidity_min,
+                summary.humidity_max,
+                summary.humidity_mean,
+                summary.gyro_magnitude_min,
+                summary.gyro_magnitude_max,
+                summary.gyro_magnitude_mean,
+            );
+        }
+
+        FreeRtos.delay_ms(sensor_config.sample_interval_ms);
     }
 }
@@ -0,0 +1,59 @@
+//! Complementary filter fusing ICM42670 accelerometer and gyro readings into
+//! a usable pitch/roll orientation estimate.
+
+/// Blend factor between the gyro-integrated angle and the accel-derived
+/// angle; closer to 1.0 trusts the (drift-prone but low-noise) gyro more.
+const ALPHA: f32 = 0.98;
+
+/// Pitch/roll angles, in degrees, fused from accelerometer and gyro data.
+pub struct ComplementaryFilter {
+    pitch_deg: f32,
+    roll_deg: f32,
+}
+
+impl ComplementaryFilter {
+    pub const fn new() -> Self {
+        Self {
+            pitch_deg: 0.0,
+            roll_deg: 0.0,
+        }
+    }
+
+    /// Folds in one iteration's accel and gyro samples.
+    ///
+    /// `accel_{x,y,z}` are normalized accelerometer readings (any consistent
+    /// unit, since only their ratios matter). `gyro_pitch_dps`/`gyro_roll_dps`
+    /// are the gyro rates, in degrees/second, about the axes that drive the
+    /// pitch and roll angles above (pitch rotates about Y, so pass the
+    /// gyro's Y rate; roll rotates about X, so pass the gyro's X rate).
+    /// `dt_s` is the elapsed time since the previous call, in seconds.
+    pub fn update(
+        &mut self,
+        accel_x: f32,
+        accel_y: f32,
+        accel_z: f32,
+        gyro_pitch_dps: f32,
+        gyro_roll_dps: f32,
+        dt_s: f32,
+    ) {
+        let accel_pitch_deg = accel_x
+            .atan2((accel_y * accel_y + accel_z * accel_z).sqrt())
+            .to_degrees();
+        let accel_roll_deg = accel_y
+            .atan2((accel_x * accel_x + accel_z * accel_z).sqrt())
+            .to_degrees();
+
+        self.pitch_deg =
+            ALPHA * (self.pitch_deg + gyro_pitch_dps * dt_s) + (1.0 - ALPHA) * accel_pitch_deg;
+        self.roll_deg =
+            ALPHA * (self.roll_deg + gyro_roll_dps * dt_s) + (1.0 - ALPHA) * accel_roll_deg;
+    }
+
+    pub fn pitch_deg(&self) -> f32 {
+        self.pitch_deg
+    }
+
+    pub fn roll_deg(&self) -> f32 {
+        self.roll_deg
+    }
+}
@@ -0,0 +1,145 @@
+//! Fixed-capacity, allocation-free aggregation for the sampling loop: running
+//! min/max over every sample since start plus a mean over the most recent
+//! samples, so a user can leave the device running and characterise a
+//! sensor's drift and noise floor over time.
+
+/// Number of most-recent samples folded into the windowed mean. Unlike the
+/// summary interval, this is a fixed buffer capacity rather than a user
+/// knob, so it stays a compile-time constant.
+const WINDOW_SIZE: usize = 20;
+
+/// Fixed-capacity ring buffer of the last `N` samples, with an incrementally
+/// maintained sum so the windowed mean never needs to rescan the buffer.
+struct RingBuffer<const N: usize> {
+    samples: [f32; N],
+    next: usize,
+    len: usize,
+    sum: f32,
+}
+
+impl<const N: usize> RingBuffer<N> {
+    const fn new() -> Self {
+        Self {
+            samples: [0.0; N],
+            next: 0,
+            len: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn push(&mut self, value: f32) {
+        if self.len < N {
+            self.len += 1;
+        } else {
+            self.sum -= self.samples[self.next];
+        }
+        self.samples[self.next] = value;
+        self.sum += value;
+        self.next = (self.next + 1) % N;
+    }
+
+    fn mean(&self) -> f32 {
+        if self.len == 0 {
+            0.0
+        } else {
+            self.sum / self.len as f32
+        }
+    }
+}
+
+/// Running min/max plus a windowed mean for a single telemetry channel.
+struct ChannelAggregator {
+    window: RingBuffer<WINDOW_SIZE>,
+    min: f32,
+    max: f32,
+}
+
+impl ChannelAggregator {
+    const fn new() -> Self {
+        Self {
+            window: RingBuffer::new(),
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+        }
+    }
+
+    fn record(&mut self, value: f32) {
+        self.window.push(value);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+}
+
+/// A snapshot of the rolling statistics for all three channels, ready to print.
+pub struct Summary {
+    pub temperature_min: f32,
+    pub temperature_max: f32,
+    pub temperature_mean: f32,
+    pub humidity_min: f32,
+    pub humidity_max: f32,
+    pub humidity_mean: f32,
+    pub gyro_magnitude_min: f32,
+    pub gyro_magnitude_max: f32,
+    pub gyro_magnitude_mean: f32,
+}
+
+/// Accumulates per-iteration temperature, humidity and gyro magnitude and
+/// produces a `Summary` every `summary_interval` recorded samples.
+pub struct Telemetry {
+    temperature: ChannelAggregator,
+    humidity: ChannelAggregator,
+    gyro_magnitude: ChannelAggregator,
+    samples: u32,
+    summary_interval: u32,
+}
+
+impl Telemetry {
+    /// `summary_interval` is how many recorded samples elapse between
+    /// `Summary` emissions; it's threaded through from `SensorConfig` so a
+    /// user can tune it the same way as the other sampling-loop knobs.
+    /// Clamped to at least 1, since 0 would make the modulo check in
+    /// `record` divide by zero.
+    pub const fn new(summary_interval: u32) -> Self {
+        Self {
+            temperature: ChannelAggregator::new(),
+            humidity: ChannelAggregator::new(),
+            gyro_magnitude: ChannelAggregator::new(),
+            samples: 0,
+            summary_interval: if summary_interval == 0 {
+                1
+            } else {
+                summary_interval
+            },
+        }
+    }
+
+    /// Folds one iteration's readings in and returns a `Summary` every
+    /// `summary_interval` samples, `None` otherwise.
+    pub fn record(
+        &mut self,
+        temperature_c: f32,
+        humidity_percent: f32,
+        gyro_magnitude: f32,
+    ) -> Option<Summary> {
+        self.temperature.record(temperature_c);
+        self.humidity.record(humidity_percent);
+        self.gyro_magnitude.record(gyro_magnitude);
+        self.samples += 1;
+
+        if self.samples % self.summary_interval == 0 {
+            Some(Summary {
+                temperature_min: self.temperature.min,
+                temperature_max: self.temperature.max,
+                temperature_mean: self.temperature.window.mean(),
+                humidity_min: self.humidity.min,
+                humidity_max: self.humidity.max,
+                humidity_mean: self.humidity.window.mean(),
+                gyro_magnitude_min: self.gyro_magnitude.min,
+                gyro_magnitude_max: self.gyro_magnitude.max,
+                gyro_magnitude_mean: self.gyro_magnitude.window.mean(),
+            })
+        } else {
+            None
+        }
+    }
+}